@@ -27,16 +27,32 @@ async fn function_handler(event: Request) -> Result<Response<Body>, LambdaError>
             return Ok(ErrorResponse::build("Seed not provided."));
         };
 
-    let mut marble = Marble::new(seed);
+    let mut marble = Marble::from_hashed(seed);
 
-    let Ok(png) = marble.render_png(1024) else {
-        return Ok(ErrorResponse::build("Failed to render marble."));
+    let wants_webp = event
+        .headers()
+        .get("accept")
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("image/webp"));
+
+    let (content_type, image) = if wants_webp {
+        let Ok(webp) = marble.render_webp(1024) else {
+            return Ok(ErrorResponse::build("Failed to render marble."));
+        };
+
+        ("image/webp", webp)
+    } else {
+        let Ok(png) = marble.render_png(1024) else {
+            return Ok(ErrorResponse::build("Failed to render marble."));
+        };
+
+        ("image/png", png)
     };
 
     let Ok(resp) = Response::builder()
         .status(200)
-        .header("content-type", "image/png")
-        .body(Body::Binary(png)) else {
+        .header("content-type", content_type)
+        .body(Body::Binary(image)) else {
             return Ok(ErrorResponse::build("Failed to build response."));
         };
 
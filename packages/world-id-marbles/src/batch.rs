@@ -0,0 +1,335 @@
+use crate::{seed::Seedable, Layer, Marble, Shape, TextureOptions, U256};
+use anyhow::{Context, Result};
+use resvg::tiny_skia::Pixmap;
+use vello::{
+    kurbo::{Affine, BezPath, Ellipse, Shape as _, Vec2},
+    peniko::{Color, Fill},
+    util::RenderContext,
+    AaConfig, RenderParams, Renderer, RendererOptions, Scene,
+};
+
+/// The marble's shuffled layers and rotation, built once per seed and reused
+/// across the GPU render instead of re-parsing an SVG string per image.
+struct MarbleGeometry {
+    layers: Vec<Layer>,
+    rotation: u32,
+}
+
+impl MarbleGeometry {
+    /// Drives the same deterministic RNG sequence as `Marble::build_svg`
+    /// (layers, shuffle, rotation, in that order) so a marble rendered
+    /// through the GPU path uses the same colors, shapes, and rotation as
+    /// its `resvg` render for the same seed.
+    fn build(marble: &mut Marble) -> Self {
+        let layers = marble.get_layers().clone();
+        let layers = marble.random_sort(layers);
+        let rotation = marble.random_number(359);
+
+        Self { layers, rotation }
+    }
+}
+
+/// Parse a `#RRGGBB` color string into its channels; any channel that is
+/// missing or not valid hex reads as 0 - `Layer::fill` is a free-form
+/// public `String`, so a caller's `with_layers()` value isn't guaranteed to
+/// be in this format the way the seed-derived palette always is.
+fn parse_hex(hex: &str) -> [u8; 3] {
+    let channel =
+        |start: usize| hex.get(start..start + 2).and_then(|s| u8::from_str_radix(s, 16).ok()).unwrap_or(0);
+
+    [channel(1), channel(3), channel(5)]
+}
+
+/// Parse an SVG `matrix(a b c d e f)` transform string into the equivalent
+/// `Affine`, returning the identity transform for anything else (including
+/// `None`, for layers with no transform).
+fn shape_affine(transform: Option<&str>) -> Affine {
+    let Some(raw) = transform else {
+        return Affine::IDENTITY;
+    };
+    let Some(inner) = raw.strip_prefix("matrix(").and_then(|s| s.strip_suffix(')')) else {
+        return Affine::IDENTITY;
+    };
+
+    let mut coeffs = [0.0; 6];
+    let mut values = inner.split([',', ' ']).filter(|s| !s.is_empty());
+    for coeff in &mut coeffs {
+        let Some(Ok(value)) = values.next().map(str::parse) else {
+            return Affine::IDENTITY;
+        };
+        *coeff = value;
+    }
+
+    Affine::new(coeffs)
+}
+
+/// A cheap, hardware-independent approximation of the SVG's
+/// `feGaussianBlur`: instead of a real blur pass, the shape is drawn several
+/// times at small offsets with decreasing alpha, smearing its edges.
+const BLUR_OFFSETS: [(f64, f64, f64); 5] = [
+    (0.0, 0.0, 1.0),
+    (6.0, 0.0, 0.35),
+    (-6.0, 0.0, 0.35),
+    (0.0, 6.0, 0.35),
+    (0.0, -6.0, 0.35),
+];
+
+fn fill_blurred(
+    scene: &mut Scene,
+    transform: Affine,
+    [r, g, b]: [u8; 3],
+    alpha: u8,
+    shape: &impl vello::kurbo::Shape,
+) {
+    for (dx, dy, alpha_mul) in BLUR_OFFSETS {
+        let a = (f64::from(alpha) * alpha_mul).round().clamp(0.0, 255.0) as u8;
+        let offset = transform * Affine::translate((dx, dy));
+
+        scene.fill(Fill::NonZero, offset, Color::rgba8(r, g, b, a), None, shape);
+    }
+}
+
+/// Render many marbles at once on the GPU, amortizing `wgpu` device setup
+/// and Vello shader compilation across the whole batch instead of paying
+/// `resvg`'s per-image CPU tessellation cost.
+///
+/// Each seed is rendered as `Marble::new(seed)`, with the default
+/// seed-derived layers and [`TextureOptions::Smooth`].
+///
+/// Falls back to the existing `resvg`-based [`Marble::render_png`] path when
+/// no GPU adapter is available, and for any marble whose texture is not
+/// `Smooth` - the GPU path only approximates the blur filter, not the
+/// noise-based texture warp. The GPU approximation isn't byte-identical to
+/// `render_png` (the blur is a cheap multi-sample offset, not a true
+/// Gaussian), but uses the same seed-derived colors, shapes, and rotation.
+/// The bytes returned for a seed therefore differ between hosts with and
+/// without a GPU adapter.
+///
+/// # Errors
+///
+/// This function will return an error if a marble cannot be rendered or its
+/// output texture cannot be read back and encoded as PNG.
+pub fn render_batch(
+    seeds: impl IntoIterator<Item = impl Seedable>,
+    size: u32,
+) -> Result<Vec<Vec<u8>>> {
+    let seeds: Vec<U256> = seeds.into_iter().map(Seedable::into).collect();
+
+    let Some(mut renderer) = pollster::block_on(GpuBatchRenderer::new()) else {
+        return seeds
+            .into_iter()
+            .map(|seed| Marble::new(seed).render_png(size))
+            .collect();
+    };
+
+    seeds
+        .into_iter()
+        .map(|seed| {
+            let mut marble = Marble::new(seed);
+
+            if !matches!(marble.texture(), TextureOptions::Smooth) {
+                return marble.render_png(size);
+            }
+
+            let geometry = MarbleGeometry::build(&mut marble);
+
+            renderer.render(&geometry, size)
+        })
+        .collect()
+}
+
+/// Owns the `wgpu` device/queue and Vello renderer for an entire batch, so
+/// device setup and shader compilation happen once instead of per-marble.
+struct GpuBatchRenderer {
+    context: RenderContext,
+    device_index: usize,
+    renderer: Renderer,
+}
+
+impl GpuBatchRenderer {
+    async fn new() -> Option<Self> {
+        let mut context = RenderContext::new().ok()?;
+        let device_index = context.device(None).await?;
+        let device_handle = &context.devices[device_index];
+
+        let renderer = Renderer::new(
+            &device_handle.device,
+            RendererOptions {
+                surface_format: None,
+                use_cpu: false,
+                antialiasing_support: vello::AaSupport::area_only(),
+                num_init_threads: None,
+            },
+        )
+        .ok()?;
+
+        Some(Self {
+            context,
+            device_index,
+            renderer,
+        })
+    }
+
+    fn render(&mut self, geometry: &MarbleGeometry, size: u32) -> Result<Vec<u8>> {
+        let mut scene = Scene::new();
+        build_scene(&mut scene, geometry, size);
+
+        let device_handle = &self.context.devices[self.device_index];
+        let device = &device_handle.device;
+        let queue = &device_handle.queue;
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("marble"),
+            size: wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        self.renderer
+            .render_to_texture(
+                device,
+                queue,
+                &scene,
+                &view,
+                &RenderParams {
+                    base_color: Color::TRANSPARENT,
+                    width: size,
+                    height: size,
+                    antialiasing_method: AaConfig::Area,
+                },
+            )
+            .map_err(|err| anyhow::anyhow!("Failed to render marble on the GPU: {err}"))?;
+
+        // Vello writes premultiplied RGBA8 into the target, matching
+        // tiny_skia's `PremultipliedColorU8` pixel layout, so the readback
+        // bytes can be copied straight into a `Pixmap` once row padding is
+        // stripped out below.
+        let bytes = read_texture_rgba(device, queue, &texture, size)?;
+
+        let mut pixmap =
+            Pixmap::new(size, size).context("Failed to create pixmap for readback")?;
+        pixmap.data_mut().copy_from_slice(&bytes);
+
+        Ok(pixmap.encode_png()?)
+    }
+}
+
+/// Copy a `size`-by-`size` RGBA8 texture into a tightly packed byte buffer,
+/// undoing wgpu's requirement that buffer rows be padded to a multiple of
+/// `COPY_BYTES_PER_ROW_ALIGNMENT`.
+fn read_texture_rgba(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    size: u32,
+) -> Result<Vec<u8>> {
+    let unpadded_bytes_per_row = size * 4;
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("marble-readback"),
+        size: u64::from(padded_bytes_per_row) * u64::from(size),
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(size),
+            },
+        },
+        wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = tx.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    rx.recv().context("Failed to map readback buffer")??;
+
+    let mapped = slice.get_mapped_range();
+    let mut out = Vec::with_capacity((unpadded_bytes_per_row * size) as usize);
+    for row in mapped.chunks(padded_bytes_per_row as usize) {
+        out.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+    }
+    drop(mapped);
+    buffer.unmap();
+
+    Ok(out)
+}
+
+/// Translate a marble's shuffled layers into Vello scene primitives, honoring
+/// each layer's own shape, transform, fill, and opacity instead of a
+/// hardcoded three-shape layout, so `with_layers` customization carries over
+/// into the GPU path.
+fn build_scene(scene: &mut Scene, geometry: &MarbleGeometry, size: u32) {
+    let scale = f64::from(size) / 80.0;
+
+    // The SVG rotates the whole marble about its local center (40, 40)
+    // before the viewBox is scaled up to the output size, so the rotation
+    // has to pivot around that local point rather than the canvas center -
+    // translate the pivot to the origin, rotate, translate back, *then*
+    // scale to canvas pixels.
+    let pivot = Vec2::new(40.0, 40.0);
+    let theta = f64::from(geometry.rotation).to_radians();
+    let transform = Affine::scale(scale)
+        * Affine::translate(pivot)
+        * Affine::rotate(theta)
+        * Affine::translate(-pivot);
+
+    scene.fill(
+        Fill::NonZero,
+        Affine::scale(scale),
+        Color::rgb8(0xF8, 0xF8, 0xF8),
+        None,
+        &vello::kurbo::Circle::new((40.0, 40.0), 40.0),
+    );
+
+    for layer in &geometry.layers {
+        let rgb = parse_hex(&layer.fill);
+        let alpha = (layer.opacity * 255.0).round().clamp(0.0, 255.0) as u8;
+
+        match &layer.shape {
+            Shape::Ellipse {
+                cx,
+                cy,
+                rx,
+                ry,
+                transform: local,
+            } => {
+                let transform = transform * shape_affine(local.as_deref());
+                let ellipse = Ellipse::new((*cx, *cy), (*rx, *ry), 0.0);
+
+                fill_blurred(scene, transform, rgb, alpha, &ellipse);
+            }
+            Shape::Path { d } => {
+                let path = BezPath::from_svg(d).unwrap_or_default();
+
+                fill_blurred(scene, transform, rgb, alpha, &path);
+            }
+        }
+    }
+}
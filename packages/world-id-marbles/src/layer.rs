@@ -0,0 +1,127 @@
+use std::fmt::Write as _;
+
+/// Escape characters that would otherwise let an attacker-controlled
+/// attribute value (a hashed username's derived color, a caller-supplied
+/// path, ...) break out of a `"..."` attribute and inject markup into the
+/// SVG handed to `usvg::Tree::from_data`.
+fn escape_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// A shape that can be composited into a marble, in the style of the
+/// `svg_fmt` crate: each variant knows how to write its own SVG markup
+/// rather than being assembled from a format-string template.
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Ellipse {
+        cx: f64,
+        cy: f64,
+        rx: f64,
+        ry: f64,
+        transform: Option<String>,
+    },
+    Path {
+        d: String,
+    },
+}
+
+impl Shape {
+    fn write_into(&self, out: &mut String, fill: &str) {
+        match self {
+            Self::Ellipse {
+                cx,
+                cy,
+                rx,
+                ry,
+                transform,
+            } => {
+                let fill = escape_attr(fill);
+                let _ = write!(
+                    out,
+                    r#"<ellipse cx="{cx}" cy="{cy}" fill="{fill}" rx="{rx}" ry="{ry}""#
+                );
+                if let Some(transform) = transform {
+                    let transform = escape_attr(transform);
+                    let _ = write!(out, r#" transform="{transform}""#);
+                }
+                out.push_str("/>");
+            }
+            Self::Path { d } => {
+                let fill = escape_attr(fill);
+                let d = escape_attr(d);
+                let _ = write!(out, r#"<path fill="{fill}" d="{d}"/>"#);
+            }
+        }
+    }
+}
+
+/// A single colored, blurred shape rendered into the marble. Callers can
+/// push their own layers (via `Marble::get_layers`) to add custom shapes,
+/// override the palette, or change how many layers are drawn.
+#[derive(Debug, Clone)]
+pub struct Layer {
+    pub shape: Shape,
+    pub fill: String,
+    pub opacity: f64,
+}
+
+impl Layer {
+    fn write_into(&self, out: &mut String) {
+        let _ = write!(out, r#"<g filter="url(#blur)" opacity="{}">"#, self.opacity);
+        self.shape.write_into(out, &self.fill);
+        out.push_str("</g>");
+    }
+}
+
+/// Write every layer's SVG markup into a single string, in draw order.
+#[must_use]
+pub fn render_layers(layers: &[Layer]) -> String {
+    let mut out = String::new();
+
+    for layer in layers {
+        layer.write_into(&mut out);
+    }
+
+    out
+}
+
+/// The marble's original three-blob look: two blurred ellipses and a
+/// blurred path, one per palette color.
+#[must_use]
+pub fn default_layers(colors: &[String; 3]) -> Vec<Layer> {
+    vec![
+        Layer {
+            shape: Shape::Ellipse {
+                cx: 33.545,
+                cy: 32.494,
+                rx: 33.545,
+                ry: 32.494,
+                transform: Some("matrix(-.48289 -.87568 .7985 -.602 9.46 74.034)".to_string()),
+            },
+            fill: colors[0].clone(),
+            opacity: 0.9,
+        },
+        Layer {
+            shape: Shape::Path {
+                d: "M78.824-16.686c17.78 14.541 4.24 87.76-2.637 82.948-4.194-2.935-9.153-27.765-22.32-38.405-8.418-6.802-23.488-1.839-33.086-1.137-24.614 1.8 40.115-58.069 58.043-43.406Z".to_string(),
+            },
+            fill: colors[1].clone(),
+            opacity: 0.8,
+        },
+        Layer {
+            shape: Shape::Ellipse {
+                cx: 39.533,
+                cy: 39.042,
+                rx: 39.533,
+                ry: 39.042,
+                transform: Some("matrix(-.2882 -.95757 .93652 -.35062 13.847 67.74)".to_string()),
+            },
+            fill: colors[2].clone(),
+            opacity: 0.8,
+        },
+    ]
+}
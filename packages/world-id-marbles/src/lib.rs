@@ -1,17 +1,28 @@
 #![warn(clippy::all, clippy::pedantic, clippy::nursery)]
 
 use anyhow::Result;
+use image::ImageEncoder;
 use indoc::formatdoc;
+use pixels::RgbaPixels;
 use resvg::{
-    tiny_skia::{Color, Pixmap, Transform},
+    tiny_skia::{Pixmap, Transform},
     usvg::{self, TreeParsing},
 };
 use seed::Seedable;
 use std::{fmt::Debug, fs, path::Path};
 
+pub use layer::{default_layers, Layer, Shape};
 pub use ruint::aliases::U256;
+pub use resvg::tiny_skia::Color;
+pub use batch::render_batch;
+pub use seed::{hash_seed, seed_from_str, try_seed};
+pub use terminal::TerminalMode;
 
+mod batch;
+mod layer;
+mod pixels;
 mod seed;
+mod terminal;
 
 const COLORS: [&str; 36] = [
     "#FF0000", "#FF2B00", "#FF5500", "#FF8000", "#FFAA00", "#FFD500", "#FFFF00", "#D4FF00",
@@ -21,22 +32,114 @@ const COLORS: [&str; 36] = [
     "#FF00D5", "#FF00AA", "#FF0080", "#FF0055",
 ];
 
+/// Default parameters used when a [`TextureOptions`] variant doesn't specify
+/// them explicitly.
+const DEFAULT_TEXTURE_OCTAVES: u8 = 3;
+const DEFAULT_TEXTURE_BASE_FREQUENCY: f64 = 0.02;
+const DEFAULT_TEXTURE_DISPLACEMENT_SCALE: f64 = 12.0;
+
+/// Controls whether `Marble::build_svg` warps the marble's color shapes
+/// through a noise-driven displacement filter, so the surface reads as
+/// veined stone instead of three blurred blobs.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum TextureOptions {
+    /// No texture filter; the original blurred-blob look.
+    #[default]
+    Smooth,
+    /// Warp with an `feTurbulence` noise field of the given shape.
+    Turbulence { octaves: u8, base_frequency: f64 },
+    /// Warp with the default noise field, scaled by `displacement_scale`.
+    Marbled { displacement_scale: f64 },
+}
+
+impl TextureOptions {
+    /// Resolve this option into `(octaves, base_frequency, displacement_scale)`,
+    /// or `None` for [`TextureOptions::Smooth`].
+    const fn params(self) -> Option<(u8, f64, f64)> {
+        match self {
+            Self::Smooth => None,
+            Self::Turbulence {
+                octaves,
+                base_frequency,
+            } => Some((octaves, base_frequency, DEFAULT_TEXTURE_DISPLACEMENT_SCALE)),
+            Self::Marbled { displacement_scale } => Some((
+                DEFAULT_TEXTURE_OCTAVES,
+                DEFAULT_TEXTURE_BASE_FREQUENCY,
+                displacement_scale,
+            )),
+        }
+    }
+}
+
 pub struct Marble {
     seed: U256,
     colors: Option<[String; 3]>,
+    layers: Option<Vec<Layer>>,
+    texture: TextureOptions,
+    svg: Option<String>,
 }
 
 impl Marble {
     /// Create a new marble with the given seed.
     ///
+    /// This is intentionally strict: a `String`/`&str` seed must parse as a
+    /// base-10 integer, and callers who want to seed from an arbitrary
+    /// string (a username, email, or nullifier hash) that may not parse
+    /// should use [`Marble::from_hashed`] instead, which never panics.
+    ///
     /// # Panics
     ///
-    /// Panics if the seed cannot be converted to a `U256`.
+    /// Panics if the seed cannot be converted to a `U256` - in particular, if
+    /// a `String`/`&str` seed isn't a valid base-10 integer.
     pub fn new(seed: impl Seedable) -> Self {
         Self {
             colors: None,
+            layers: None,
             seed: seed.into(),
+            texture: TextureOptions::default(),
+            svg: None,
+        }
+    }
+
+    /// Create a new marble from an arbitrary string, such as a username,
+    /// email, or World ID nullifier hash.
+    ///
+    /// Unlike `Marble::new`, this never panics: strings that parse cleanly
+    /// as a base-10 or `0x`-prefixed hex integer are used as-is, and
+    /// anything else is hashed into a seed. Identical strings always
+    /// produce identical marbles.
+    #[must_use]
+    pub fn from_hashed(value: &str) -> Self {
+        Self::new(seed::seed_from_str(value))
+    }
+
+    /// Set the texture filter applied to the marble's surface.
+    #[must_use]
+    pub fn with_texture(mut self, texture: TextureOptions) -> Self {
+        self.texture = texture;
+        self
+    }
+
+    /// Replace the marble's layers entirely, for callers that want to
+    /// provide their own shapes or palette up front.
+    #[must_use]
+    pub fn with_layers(mut self, layers: Vec<Layer>) -> Self {
+        self.layers = Some(layers);
+        self
+    }
+
+    /// Get a mutable reference to the marble's layers, building the default
+    /// three-blob set from the seed-derived palette on first access.
+    ///
+    /// Callers can push custom layers, reorder or remove the defaults, or
+    /// override their fill colors before calling `build_svg`/`render_png`.
+    pub fn get_layers(&mut self) -> &mut Vec<Layer> {
+        if self.layers.is_none() {
+            let colors = self.get_colors().clone();
+            self.layers = Some(layer::default_layers(&colors));
         }
+
+        self.layers.as_mut().unwrap_or_else(|| unreachable!())
     }
 
     fn random_number<T, E>(&mut self, max: T) -> T
@@ -68,6 +171,30 @@ impl Marble {
         COLORS[self.random_number(COLORS.len())]
     }
 
+    /// Build the `<filter>` definition for the current [`TextureOptions`],
+    /// plus the seed consumed from the same deterministic RNG stream as the
+    /// rest of `build_svg`. Returns `None` for `TextureOptions::Smooth`.
+    fn build_texture_filter(&mut self) -> Option<String> {
+        let (octaves, base_frequency, displacement_scale) = self.texture.params()?;
+        let seed: u32 = self.random_number(1000);
+
+        Some(formatdoc!(
+            r#"
+                <filter id="texture" width="300" height="300" x="0" y="0" color-interpolation-filters="sRGB" filterUnits="userSpaceOnUse">
+                    <feTurbulence type="fractalNoise" numOctaves="{octaves}" seed="{seed}" baseFrequency="{base_frequency}" result="noise" />
+                    <feDisplacementMap in="SourceGraphic" in2="noise" scale="{displacement_scale}" xChannelSelector="R" yChannelSelector="G" />
+                </filter>
+            "#
+        ))
+    }
+
+    /// The marble's configured [`TextureOptions`], for the GPU batch
+    /// renderer to decide whether it can approximate this marble or needs
+    /// to fall back to the exact `resvg` render.
+    pub(crate) const fn texture(&self) -> TextureOptions {
+        self.texture
+    }
+
     pub fn get_colors(&mut self) -> &[String; 3] {
         if self.colors.is_none() {
             self.colors = Some([
@@ -80,42 +207,32 @@ impl Marble {
         self.colors.as_ref().unwrap_or_else(|| unreachable!())
     }
 
-    /// Build the SVG for the marble.
+    /// Build the SVG for the marble, caching it on first call like
+    /// `get_colors`/`get_layers` so repeated calls - e.g. `render_png`
+    /// followed by `render_webp` on the same `Marble` - don't re-draw the
+    /// rotation, layer shuffle, or texture seed from the (by-then-advanced)
+    /// RNG and produce a different-looking marble for the same seed.
     #[must_use]
     pub fn build_svg(&mut self) -> String {
-        let colors = self.get_colors();
-
-        let shapes = vec![
-            formatdoc!(
-                r#"
-                <g filter="url(#blur)" opacity=".9">
-                    <ellipse cx="33.545" cy="32.494" fill="{color}" rx="33.545" ry="32.494" transform="matrix(-.48289 -.87568 .7985 -.602 9.46 74.034)"/>
-                </g>
-            "#,
-                color = colors[0]
-            ),
-            formatdoc!(
-                r#"
-                <g filter="url(#blur)" opacity=".8">
-                    <path fill="{color}" d="M78.824-16.686c17.78 14.541 4.24 87.76-2.637 82.948-4.194-2.935-9.153-27.765-22.32-38.405-8.418-6.802-23.488-1.839-33.086-1.137-24.614 1.8 40.115-58.069 58.043-43.406Z"/>
-                </g>
-            "#,
-                color = colors[1]
-            ),
-            formatdoc!(
-                r#"
-                <g filter="url(#blur)" opacity=".8">
-                    <ellipse cx="39.533" cy="39.042" fill="{color}" rx="39.533" ry="39.042" transform="matrix(-.2882 -.95757 .93652 -.35062 13.847 67.74)" />
-                </g>
-            "#,
-                color = colors[2]
-            ),
-        ];
-
-        formatdoc!(
+        if let Some(svg) = &self.svg {
+            return svg.clone();
+        }
+
+        let layers = self.get_layers().clone();
+        let layers = self.random_sort(layers);
+        let shapes = layer::render_layers(&layers);
+
+        let rotation: u32 = self.random_number(359);
+        let texture_filter = self.build_texture_filter();
+        let texture_attr = texture_filter
+            .is_some()
+            .then(|| r#" filter="url(#texture)""#)
+            .unwrap_or_default();
+
+        let svg = formatdoc!(
             r##"
                 <svg xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 80 80" transform="rotate({rotation} 40 40)">
-                    <g clip-path="url(#a)">
+                    <g clip-path="url(#a)"{texture_attr}>
                         <circle cx="40" cy="40" r="40" fill="#F8F8F8" />
                         {shapes}
                     </g>
@@ -123,42 +240,55 @@ impl Marble {
                         <filter id="blur" width="300" height="300" x="0" y="0" color-interpolation-filters="sRGB" filterUnits="userSpaceOnUse">
                             <feGaussianBlur result="effect1_foregroundBlur_557_59789" stdDeviation="9.6" />
                         </filter>
+                        {texture_filter}
                         <clipPath id="a">
                             <rect width="80" height="80" fill="#fff" rx="40" />
                         </clipPath>
                     </defs>
                 </svg>
         "##,
-            shapes = self.random_sort(shapes).join(""),
-            rotation = self.random_number(359),
-        )
+            texture_filter = texture_filter.unwrap_or_default(),
+        );
+
+        self.svg = Some(svg);
+        self.svg.clone().unwrap_or_else(|| unreachable!())
     }
 
-    /// Render the marble as a PNG.
-    /// The PNG is returned as a vector of bytes.
+    /// Build the SVG and rasterize it to a `width` by `height` pixmap.
     ///
     /// # Errors
     ///
-    /// This function will return an error if the marble cannot be rendered.
-    /// This can happen if the SVG fails to be parsed or the PNG cannot be encoded.
-    pub fn render_png(&mut self, size: u32) -> Result<Vec<u8>> {
+    /// This function will return an error if the SVG fails to parse or the
+    /// pixmap cannot be created or rendered into.
+    fn render_pixmap(&mut self, width: u32, height: u32) -> Result<Pixmap> {
         let svg = self.build_svg();
         let tree = usvg::Tree::from_data(svg.as_bytes(), &usvg::Options::default())?;
 
-        let mut pixmap = Pixmap::new(size, size).ok_or_else(|| {
-            anyhow::anyhow!("Failed to create pixmap with size {}x{}", size, size)
+        let mut pixmap = Pixmap::new(width, height).ok_or_else(|| {
+            anyhow::anyhow!("Failed to create pixmap with size {}x{}", width, height)
         })?;
         pixmap.fill(Color::TRANSPARENT);
 
         resvg::render(
             &tree,
-            resvg::FitTo::Width(size),
+            resvg::FitTo::Size(width, height),
             Transform::default(),
             pixmap.as_mut(),
         )
         .ok_or_else(|| anyhow::anyhow!("Failed to render SVG"))?;
 
-        Ok(pixmap.encode_png()?)
+        Ok(pixmap)
+    }
+
+    /// Render the marble as a PNG.
+    /// The PNG is returned as a vector of bytes.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the marble cannot be rendered.
+    /// This can happen if the SVG fails to be parsed or the PNG cannot be encoded.
+    pub fn render_png(&mut self, size: u32) -> Result<Vec<u8>> {
+        Ok(self.render_pixmap(size, size)?.encode_png()?)
     }
 
     /// Save the marble as a PNG.
@@ -171,4 +301,113 @@ impl Marble {
 
         Ok(())
     }
+
+    /// Render the marble as straight (non-premultiplied) RGBA bytes, along
+    /// with the pixmap's width and height.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the marble cannot be rendered.
+    pub fn render_rgba(&mut self, size: u32) -> Result<(Vec<u8>, u32, u32)> {
+        let pixmap = self.render_pixmap(size, size)?;
+        let bytes = RgbaPixels::new(&pixmap).collect();
+
+        Ok((bytes, pixmap.width(), pixmap.height()))
+    }
+
+    /// Render the marble as a JPEG at the given quality (1-100).
+    ///
+    /// JPEG has no alpha channel, so transparent pixels are flattened
+    /// against a white background before encoding.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the marble cannot be rendered
+    /// or the JPEG cannot be encoded.
+    pub fn render_jpeg(&mut self, size: u32, quality: u8) -> Result<Vec<u8>> {
+        let (rgba, width, height) = self.render_rgba(size)?;
+        let rgb = flatten_rgb_on_white(&rgba);
+
+        let mut out = Vec::new();
+        image::codecs::jpeg::JpegEncoder::new_with_quality(&mut out, quality).write_image(
+            &rgb,
+            width,
+            height,
+            image::ColorType::Rgb8,
+        )?;
+
+        Ok(out)
+    }
+
+    /// Render the marble as a (lossless) WebP image.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the marble cannot be rendered
+    /// or the WebP image cannot be encoded.
+    pub fn render_webp(&mut self, size: u32) -> Result<Vec<u8>> {
+        let (rgba, width, height) = self.render_rgba(size)?;
+
+        let mut out = Vec::new();
+        image::codecs::webp::WebPEncoder::new(&mut out).write_image(
+            &rgba,
+            width,
+            height,
+            image::ColorType::Rgba8,
+        )?;
+
+        Ok(out)
+    }
+
+    /// Render the marble as text that can be printed directly to a terminal,
+    /// for previewing over SSH or in CI logs without writing a file.
+    ///
+    /// In [`TerminalMode::HalfBlock`] mode `columns` and `rows` are measured
+    /// in terminal character cells: each cell packs two vertically
+    /// adjacent pixels (the top pixel sets the ANSI foreground, the bottom
+    /// sets the background), so the marble is rasterized at `columns` by
+    /// `rows * 2` pixels. In [`TerminalMode::Sixel`] mode `columns` and
+    /// `rows` are the sixel image's width and height in pixels, not cells.
+    ///
+    /// Neither mode supports alpha, so transparent pixels are flattened
+    /// against `background` before encoding. The returned string resets the
+    /// SGR state at the end of each line.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the marble cannot be rendered.
+    pub fn render_terminal(
+        &mut self,
+        columns: u32,
+        rows: u32,
+        mode: TerminalMode,
+        background: Color,
+    ) -> Result<String> {
+        let height = match mode {
+            TerminalMode::HalfBlock => rows * 2,
+            TerminalMode::Sixel => rows,
+        };
+
+        let pixmap = self.render_pixmap(columns, height)?;
+
+        Ok(match mode {
+            TerminalMode::Sixel => terminal::encode_sixel(&pixmap, background),
+            TerminalMode::HalfBlock => terminal::encode_half_blocks(&pixmap, background),
+        })
+    }
+}
+
+/// Composite straight RGBA bytes over an opaque white background and drop
+/// the alpha channel, for encoders that don't support transparency.
+fn flatten_rgb_on_white(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|pixel| {
+            let alpha = f32::from(pixel[3]) / 255.0;
+            let over = |channel: u8| -> u8 {
+                (f32::from(channel) * alpha + 255.0 * (1.0 - alpha)).round() as u8
+            };
+
+            [over(pixel[0]), over(pixel[1]), over(pixel[2])]
+        })
+        .collect()
 }
@@ -0,0 +1,53 @@
+use num_traits::AsPrimitive;
+use resvg::tiny_skia::Pixmap;
+
+/// Iterates a rasterized [`Pixmap`]'s pixels as straight (non-premultiplied)
+/// RGBA bytes, four `u8`s per pixel, for handing to `image`'s encoders.
+///
+/// `tiny_skia` stores premultiplied alpha, so demultiplying each channel
+/// goes through `f32` and casts back down via [`AsPrimitive<u8>`].
+pub struct RgbaPixels<'a> {
+    pixels: std::slice::Iter<'a, resvg::tiny_skia::PremultipliedColorU8>,
+    pending: std::vec::IntoIter<u8>,
+}
+
+impl<'a> RgbaPixels<'a> {
+    pub fn new(pixmap: &'a Pixmap) -> Self {
+        Self {
+            pixels: pixmap.pixels().iter(),
+            pending: Vec::new().into_iter(),
+        }
+    }
+}
+
+impl Iterator for RgbaPixels<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some(byte) = self.pending.next() {
+            return Some(byte);
+        }
+
+        let pixel = self.pixels.next()?;
+        let alpha = pixel.alpha();
+
+        let demultiply = |premultiplied: u8| -> u8 {
+            if alpha == 0 {
+                0
+            } else {
+                let straight: f32 = f32::from(premultiplied) * 255.0 / f32::from(alpha);
+                straight.round().min(255.0).as_()
+            }
+        };
+
+        self.pending = vec![
+            demultiply(pixel.red()),
+            demultiply(pixel.green()),
+            demultiply(pixel.blue()),
+            alpha,
+        ]
+        .into_iter();
+
+        self.pending.next()
+    }
+}
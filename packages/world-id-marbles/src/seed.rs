@@ -1,9 +1,39 @@
+use sha2::{Digest, Sha256};
+
 pub use ruint::aliases::U256;
 
 pub trait Seedable {
     fn into(self) -> U256;
 }
 
+/// Parse `value` as a seed without hashing it: a base-10 integer, or a
+/// `0x`-prefixed hex integer. Returns `None` for anything else, in which
+/// case the caller should fall back to [`hash_seed`].
+#[must_use]
+pub fn try_seed(value: &str) -> Option<U256> {
+    value.strip_prefix("0x").map_or_else(
+        || U256::from_str_radix(value, 10).ok(),
+        |hex| U256::from_str_radix(hex, 16).ok(),
+    )
+}
+
+/// Hash `value` with SHA-256 and interpret the digest as a big-endian
+/// `U256`, so arbitrary strings (usernames, emails, nullifier hashes) can be
+/// used as seeds. Identical strings always hash to the same seed.
+#[must_use]
+pub fn hash_seed(value: &str) -> U256 {
+    let digest: [u8; 32] = Sha256::digest(value.as_bytes()).into();
+
+    U256::from_be_bytes(digest)
+}
+
+/// Parse `value` as a numeric seed if possible, otherwise hash it. Unlike
+/// `U256::from_str_radix(..).unwrap()`, this never panics.
+#[must_use]
+pub fn seed_from_str(value: &str) -> U256 {
+    try_seed(value).unwrap_or_else(|| hash_seed(value))
+}
+
 impl Seedable for U256 {
     fn into(self) -> U256 {
         self
@@ -0,0 +1,145 @@
+use resvg::tiny_skia::{Color, Pixmap};
+use std::collections::{BTreeMap, HashMap};
+
+/// Terminal image output mode for [`crate::Marble::render_terminal`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalMode {
+    /// Encode as a DEC sixel graphic, for terminals that advertise sixel support.
+    Sixel,
+    /// Fall back to Unicode upper-half-block characters with 24-bit ANSI colors.
+    HalfBlock,
+}
+
+/// Flatten the pixel at `(x, y)` against `background`, returning straight RGB.
+///
+/// `tiny_skia` stores premultiplied alpha, so compositing over an opaque
+/// background is just `premultiplied + background * (1 - alpha)`.
+fn flatten(pixmap: &Pixmap, x: u32, y: u32, background: Color) -> (u8, u8, u8) {
+    let Some(pixel) = pixmap.pixel(x, y) else {
+        return background_rgb(background);
+    };
+
+    let alpha = f32::from(pixel.alpha()) / 255.0;
+    let (bg_r, bg_g, bg_b) = background_rgb(background);
+
+    let over = |premultiplied: u8, bg: u8| -> u8 {
+        (f32::from(premultiplied) + f32::from(bg) * (1.0 - alpha)).round() as u8
+    };
+
+    (
+        over(pixel.red(), bg_r),
+        over(pixel.green(), bg_g),
+        over(pixel.blue(), bg_b),
+    )
+}
+
+fn background_rgb(background: Color) -> (u8, u8, u8) {
+    let color = background.to_color_u8();
+
+    (color.red(), color.green(), color.blue())
+}
+
+/// Downsample `pixmap` to upper-half-block glyphs, packing two pixel rows
+/// into each terminal cell via distinct foreground/background colors.
+pub fn encode_half_blocks(pixmap: &Pixmap, background: Color) -> String {
+    let mut out = String::new();
+
+    for y in (0..pixmap.height()).step_by(2) {
+        for x in 0..pixmap.width() {
+            let (tr, tg, tb) = flatten(pixmap, x, y, background);
+            let (br, bg, bb) = if y + 1 < pixmap.height() {
+                flatten(pixmap, x, y + 1, background)
+            } else {
+                background_rgb(background)
+            };
+
+            out.push_str(&format!(
+                "\x1b[38;2;{tr};{tg};{tb}m\x1b[48;2;{br};{bg};{bb}m\u{2580}"
+            ));
+        }
+
+        out.push_str("\x1b[0m\n");
+    }
+
+    out
+}
+
+/// Convert an 8-bit channel to the 0-100 percentage scale sixel color
+/// registers use.
+fn sixel_percent(channel: u8) -> u32 {
+    (u32::from(channel) * 100 + 127) / 255
+}
+
+/// Encode `pixmap` as a DEC sixel graphic, flattening alpha against
+/// `background` first since sixel has no transparency.
+///
+/// Each distinct flattened color is assigned its own color register on
+/// first use (DECGCI); colors are not reduced to a fixed palette size.
+/// Pixels are emitted six rows at a time with run-length compression (`!<count><char>`)
+/// as is conventional for sixel encoders.
+pub fn encode_sixel(pixmap: &Pixmap, background: Color) -> String {
+    let width = pixmap.width() as usize;
+
+    let mut palette = Vec::new();
+    let mut palette_index = HashMap::new();
+
+    let mut out = String::new();
+    out.push_str("\x1bPq");
+
+    for band_start in (0..pixmap.height()).step_by(6) {
+        let band_height = (pixmap.height() - band_start).min(6);
+        let mut band_colors: BTreeMap<usize, Vec<u8>> = BTreeMap::new();
+
+        for x in 0..pixmap.width() {
+            for dy in 0..band_height {
+                let rgb = flatten(pixmap, x, band_start + dy, background);
+                let index = *palette_index.entry(rgb).or_insert_with(|| {
+                    palette.push(rgb);
+                    palette.len() - 1
+                });
+
+                let bits = band_colors
+                    .entry(index)
+                    .or_insert_with(|| vec![0u8; width]);
+                bits[x as usize] |= 1 << dy;
+            }
+        }
+
+        for (index, bits) in &band_colors {
+            let (r, g, b) = palette[*index];
+            out.push_str(&format!(
+                "#{index};2;{};{};{}",
+                sixel_percent(r),
+                sixel_percent(g),
+                sixel_percent(b)
+            ));
+
+            let mut i = 0;
+            while i < bits.len() {
+                let value = bits[i];
+                let mut run = 1;
+                while i + run < bits.len() && bits[i + run] == value {
+                    run += 1;
+                }
+
+                let glyph = char::from(0x3f + value);
+                if run > 3 {
+                    out.push_str(&format!("!{run}{glyph}"));
+                } else {
+                    for _ in 0..run {
+                        out.push(glyph);
+                    }
+                }
+
+                i += run;
+            }
+
+            out.push('$');
+        }
+
+        out.push('-');
+    }
+
+    out.push_str("\x1b\\");
+    out
+}